@@ -0,0 +1,163 @@
+/*
+    The simple image viewer.
+    Copyright (C) 2024 Sifi Takashina <sifyfy@sifyfy.dev>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Reading pages directly out of ZIP/CBZ comic archives, so a `.cbz` can be
+//! browsed the same way as a directory of loose image files.
+
+use crate::decode;
+use std::cmp::Ordering;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// True when `path`'s extension marks it as a comic archive this module can
+/// open (`.zip` or `.cbz`).
+pub fn is_archive_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip") || ext.eq_ignore_ascii_case("cbz"))
+}
+
+/// A ZIP/CBZ archive whose image entries can be paged through like a
+/// directory of files.
+#[derive(Debug, Clone)]
+pub struct ComicArchive {
+    path: PathBuf,
+    entries: Vec<String>,
+}
+
+impl ComicArchive {
+    /// Opens `path` and lists its image entries, naturally sorted so
+    /// `page2` comes before `page10`.
+    pub fn open(path: &Path) -> Option<Self> {
+        let file = std::fs::File::open(path).ok()?;
+        let mut zip = zip::ZipArchive::new(file).ok()?;
+
+        let mut entries: Vec<String> = (0..zip.len())
+            .filter_map(|index| {
+                let entry = zip.by_index(index).ok()?;
+                let name = entry.name().to_string();
+                let extension = Path::new(&name).extension()?.to_str()?.to_ascii_lowercase();
+                decode::is_supported_extension(&extension).then_some(name)
+            })
+            .collect();
+        entries.sort_by(|a, b| natural_cmp(a, b));
+
+        Some(Self {
+            path: path.to_owned(),
+            entries,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // Kept alongside `len` for clippy's `len_without_is_empty`; nothing in
+    // this crate needs it yet.
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entry_name(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Extracts the bytes of entry `index` in-memory.
+    pub fn read_entry(&self, index: usize) -> Option<Vec<u8>> {
+        let name = self.entries.get(index)?;
+        let file = std::fs::File::open(&self.path).ok()?;
+        let mut zip = zip::ZipArchive::new(file).ok()?;
+        let mut entry = zip.by_name(name).ok()?;
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut bytes).ok()?;
+        Some(bytes)
+    }
+}
+
+/// Compares `a` and `b` the way a human would order paginated filenames,
+/// treating runs of digits as numbers rather than comparing them
+/// character-by-character (so `"page2"` sorts before `"page10"`).
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ac), Some(bc)) if ac.is_ascii_digit() && bc.is_ascii_digit() => {
+                match take_number(&mut a).cmp(&take_number(&mut b)) {
+                    Ordering::Equal => continue,
+                    other => return other,
+                }
+            }
+            (Some(ac), Some(bc)) => {
+                a.next();
+                b.next();
+                match ac.cmp(&bc) {
+                    Ordering::Equal => continue,
+                    other => return other,
+                }
+            }
+        }
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> u64 {
+    let mut value = 0u64;
+    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+        value = value * 10 + digit as u64;
+        chars.next();
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_runs_sort_numerically_not_lexically() {
+        assert_eq!(natural_cmp("page2", "page10"), Ordering::Less);
+        assert_eq!(natural_cmp("page10", "page2"), Ordering::Greater);
+        assert_eq!(natural_cmp("page02", "page2"), Ordering::Equal);
+    }
+
+    #[test]
+    fn plain_text_sorts_lexically() {
+        assert_eq!(natural_cmp("cover", "page1"), Ordering::Less);
+        assert_eq!(natural_cmp("a", "a"), Ordering::Equal);
+    }
+
+    #[test]
+    fn a_shorter_prefix_sorts_before_its_longer_extension() {
+        assert_eq!(natural_cmp("page1", "page1.jpg"), Ordering::Less);
+    }
+
+    #[test]
+    fn sorting_a_volume_orders_pages_the_way_a_reader_expects() {
+        let mut names = vec!["page10.jpg", "page1.jpg", "page2.jpg", "cover.jpg"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            vec!["cover.jpg", "page1.jpg", "page2.jpg", "page10.jpg"]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+/*
+    The simple image viewer.
+    Copyright (C) 2024 Sifi Takashina <sifyfy@sifyfy.dev>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! LRU cache of decoded images, shared between the UI thread and the
+//! background threads that preload `current_index ± 1`.
+//!
+//! A monotonically increasing generation counter lets a preload that's still
+//! running when the user navigates again notice it's stale: its result is
+//! dropped instead of being inserted, so evicted/superseded pages never
+//! clobber the cache with work nobody wants anymore.
+
+use image::RgbaImage;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Total decoded pixel memory the cache is allowed to hold. Bounding by
+/// bytes rather than entry count keeps a handful of 50-megapixel photos from
+/// blowing the budget a folder of small images would stay well under.
+const MAX_CACHE_BYTES: usize = 256 * 1024 * 1024;
+
+#[derive(Debug, Default)]
+struct Lru {
+    entries: HashMap<PathBuf, RgbaImage>,
+    recency: VecDeque<PathBuf>,
+    total_bytes: usize,
+}
+
+impl Lru {
+    fn get(&mut self, path: &Path) -> Option<RgbaImage> {
+        let image = self.entries.get(path)?.clone();
+        self.touch(path);
+        Some(image)
+    }
+
+    fn insert(&mut self, path: PathBuf, image: RgbaImage) {
+        if self.entries.contains_key(&path) {
+            self.touch(&path);
+            return;
+        }
+        self.total_bytes += byte_size(&image);
+        self.entries.insert(path.clone(), image);
+        self.recency.push_back(path);
+        self.evict_over_budget();
+    }
+
+    fn touch(&mut self, path: &Path) {
+        if let Some(pos) = self.recency.iter().position(|entry| entry == path) {
+            let path = self.recency.remove(pos).expect("pos came from this deque");
+            self.recency.push_back(path);
+        }
+    }
+
+    fn evict_over_budget(&mut self) {
+        while self.total_bytes > MAX_CACHE_BYTES {
+            let Some(lru_path) = self.recency.pop_front() else {
+                break;
+            };
+            if let Some(image) = self.entries.remove(&lru_path) {
+                self.total_bytes -= byte_size(&image);
+            }
+        }
+    }
+}
+
+fn byte_size(image: &RgbaImage) -> usize {
+    image.width() as usize * image.height() as usize * 4
+}
+
+/// An LRU cache plus the generation counter used to discard stale preloads.
+/// Cheap to share across threads: `get`/`insert_if_current` take `&self` and
+/// lock internally.
+#[derive(Debug, Default)]
+pub struct PreloadCache {
+    lru: Mutex<Lru>,
+    generation: AtomicU64,
+}
+
+impl PreloadCache {
+    pub fn get(&self, path: &Path) -> Option<RgbaImage> {
+        self.lru.lock().unwrap().get(path)
+    }
+
+    /// Invalidates any preload still in flight and returns the new
+    /// generation, to be captured by newly spawned preload tasks.
+    pub fn bump_generation(&self) -> u64 {
+        self.generation.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Inserts `image` unless `generation` has since been superseded by a
+    /// newer navigation, in which case the decode is silently discarded.
+    pub fn insert_if_current(&self, generation: u64, path: PathBuf, image: RgbaImage) {
+        if self.generation.load(Ordering::SeqCst) == generation {
+            self.lru.lock().unwrap().insert(path, image);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiny_image(shade: u8) -> RgbaImage {
+        RgbaImage::from_pixel(2, 2, image::Rgba([shade, shade, shade, 255]))
+    }
+
+    #[test]
+    fn get_touches_recency_so_it_is_evicted_last() {
+        let mut lru = Lru::default();
+        lru.insert(PathBuf::from("a"), tiny_image(1));
+        lru.insert(PathBuf::from("b"), tiny_image(2));
+
+        lru.get(Path::new("a"));
+
+        assert_eq!(
+            Vec::from(lru.recency),
+            vec![PathBuf::from("b"), PathBuf::from("a")]
+        );
+    }
+
+    #[test]
+    fn inserting_an_existing_path_touches_it_instead_of_duplicating() {
+        let mut lru = Lru::default();
+        lru.insert(PathBuf::from("a"), tiny_image(1));
+        lru.insert(PathBuf::from("b"), tiny_image(2));
+        lru.insert(PathBuf::from("a"), tiny_image(9));
+
+        assert_eq!(lru.entries.len(), 2);
+        assert_eq!(
+            Vec::from(lru.recency.clone()),
+            vec![PathBuf::from("b"), PathBuf::from("a")]
+        );
+        // Re-inserting an already-cached path is a touch, not a replace.
+        assert_eq!(lru.get(Path::new("a")).unwrap().get_pixel(0, 0)[0], 1);
+    }
+
+    #[test]
+    fn eviction_drops_the_least_recently_used_entry_first() {
+        let mut lru = Lru {
+            total_bytes: MAX_CACHE_BYTES - byte_size(&tiny_image(0)),
+            ..Lru::default()
+        };
+        lru.insert(PathBuf::from("old"), tiny_image(1));
+        lru.insert(PathBuf::from("new"), tiny_image(2));
+
+        assert!(lru.get(Path::new("old")).is_none());
+        assert!(lru.get(Path::new("new")).is_some());
+    }
+}
@@ -0,0 +1,138 @@
+/*
+    The simple image viewer.
+    Copyright (C) 2024 Sifi Takashina <sifyfy@sifyfy.dev>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Keyboard shortcuts and other user-tunable settings, read from a TOML
+//! file in the platform config directory so they can be changed without a
+//! rebuild.
+
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// The default value of [`KeyBindings::similarity_threshold`]: the maximum
+/// dHash Hamming distance "Find Similar" will still treat as a match.
+const DEFAULT_SIMILARITY_THRESHOLD: u32 = 10;
+
+/// The key chord bound to each action `MainWindow`'s `FocusScope` reacts
+/// to, plus the handful of other settings a user might want to tweak
+/// without rebuilding. Each key-binding value is either a printable
+/// character (`"+"`, `"0"`) or one of the named keys [`resolve_key_text`]
+/// understands (`"Left"`, `"Home"`, ...).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub previous: String,
+    pub next: String,
+    pub zoom_in: String,
+    pub zoom_out: String,
+    pub zoom_reset: String,
+    pub first: String,
+    pub last: String,
+    /// Maximum dHash Hamming distance (of a possible 64) for "Find Similar"
+    /// to consider two images a match; lower is stricter.
+    pub similarity_threshold: u32,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            previous: "Left".to_string(),
+            next: "Right".to_string(),
+            zoom_in: "+".to_string(),
+            zoom_out: "-".to_string(),
+            zoom_reset: "0".to_string(),
+            first: "Home".to_string(),
+            last: "End".to_string(),
+            similarity_threshold: DEFAULT_SIMILARITY_THRESHOLD,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Reads `keybindings.toml` from the platform config directory (e.g.
+    /// `~/.config/image-viewer/keybindings.toml` on Linux), falling back to
+    /// [`KeyBindings::default`] when the file is absent, unreadable, or not
+    /// valid TOML. Fields omitted from the file keep their default value.
+    pub fn load() -> Self {
+        Self::config_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn config_path() -> Option<PathBuf> {
+        Some(
+            dirs::config_dir()?
+                .join("image-viewer")
+                .join("keybindings.toml"),
+        )
+    }
+}
+
+/// Resolves a key name from a [`KeyBindings`] field to the text Slint's
+/// `KeyEvent.text` carries for that key, so it can be compared directly
+/// against the event in `MainWindow`'s `key-pressed` handler. Unrecognized
+/// names are passed through unchanged, which is what a literal printable
+/// character like `"+"` needs.
+pub fn resolve_key_text(name: &str) -> String {
+    let key = match name {
+        "Left" => slint::platform::Key::LeftArrow,
+        "Right" => slint::platform::Key::RightArrow,
+        "Up" => slint::platform::Key::UpArrow,
+        "Down" => slint::platform::Key::DownArrow,
+        "Home" => slint::platform::Key::Home,
+        "End" => slint::platform::Key::End,
+        other => return other.to_string(),
+    };
+    slint::SharedString::from(key).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_keys_resolve_to_the_same_text_as_the_slint_key() {
+        assert_eq!(
+            resolve_key_text("Left"),
+            slint::SharedString::from(slint::platform::Key::LeftArrow).to_string()
+        );
+        assert_eq!(
+            resolve_key_text("End"),
+            slint::SharedString::from(slint::platform::Key::End).to_string()
+        );
+    }
+
+    #[test]
+    fn an_unrecognized_name_passes_through_unchanged() {
+        assert_eq!(resolve_key_text("+"), "+");
+        assert_eq!(resolve_key_text("0"), "0");
+    }
+
+    #[test]
+    fn default_key_bindings_match_the_documented_defaults() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.previous, "Left");
+        assert_eq!(bindings.next, "Right");
+        assert_eq!(bindings.zoom_in, "+");
+        assert_eq!(bindings.zoom_out, "-");
+        assert_eq!(bindings.zoom_reset, "0");
+        assert_eq!(bindings.first, "Home");
+        assert_eq!(bindings.last, "End");
+        assert_eq!(bindings.similarity_threshold, DEFAULT_SIMILARITY_THRESHOLD);
+    }
+}
@@ -0,0 +1,160 @@
+/*
+    The simple image viewer.
+    Copyright (C) 2024 Sifi Takashina <sifyfy@sifyfy.dev>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Decoding for file types that aren't handled by the `image` crate directly.
+//!
+//! Each decoder lives behind its own Cargo feature so a build only pulls in
+//! the dependencies it needs. [`is_supported_extension`], [`decode`], and
+//! [`decode_from_memory`] are the entry points `main`, [`crate::ImageViewer`],
+//! and [`crate::archive`] call into.
+
+use image::{DynamicImage, RgbaImage};
+use std::path::Path;
+
+/// Whether `extension` (lowercase, no leading dot) is one `decode` can handle,
+/// given the features this binary was built with.
+pub fn is_supported_extension(extension: &str) -> bool {
+    match extension {
+        "png" | "jpg" | "jpeg" => true,
+        #[cfg(feature = "avif")]
+        "avif" => true,
+        #[cfg(feature = "heif")]
+        "heif" | "heic" => true,
+        #[cfg(feature = "webp")]
+        "webp" => true,
+        #[cfg(feature = "svg")]
+        "svg" => true,
+        _ => false,
+    }
+}
+
+/// True when `path` is a vector image that should be re-rasterized whenever
+/// the target size changes, rather than decoded once and cached.
+pub fn is_vector(path: &Path) -> bool {
+    #[cfg(feature = "svg")]
+    {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+    }
+    #[cfg(not(feature = "svg"))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+/// Decodes `path` to an RGBA8 image by reading it from disk and dispatching
+/// on its extension. `target_width`/`target_height` are used only for
+/// resolution-independent formats (currently SVG), which are rasterized
+/// directly to fit within that size, keeping their aspect ratio, instead of
+/// being decoded once and scaled.
+pub fn decode(path: &Path, target_width: u32, target_height: u32) -> Option<RgbaImage> {
+    let extension = extension_of(path);
+    let data = std::fs::read(path).ok()?;
+    decode_from_memory(&data, &extension, target_width, target_height)
+}
+
+/// Decodes an in-memory buffer to an RGBA8 image, dispatching on `extension`
+/// (lowercase, no leading dot) the same way [`decode`] does. Used for
+/// archive entries, which are extracted to memory rather than written to
+/// disk.
+#[cfg_attr(not(feature = "svg"), allow(unused_variables))]
+pub fn decode_from_memory(
+    data: &[u8],
+    extension: &str,
+    target_width: u32,
+    target_height: u32,
+) -> Option<RgbaImage> {
+    match extension {
+        #[cfg(feature = "svg")]
+        "svg" => decode_svg(data, target_width, target_height),
+        #[cfg(feature = "avif")]
+        "avif" => decode_avif(data),
+        #[cfg(feature = "heif")]
+        "heif" | "heic" => decode_heif(data),
+        #[cfg(feature = "webp")]
+        "webp" => decode_webp(data),
+        _ => image::load_from_memory(data)
+            .ok()
+            .map(DynamicImage::into_rgba8),
+    }
+}
+
+/// Lowercased extension (no leading dot) of `path`, or the empty string when
+/// it has none.
+pub(crate) fn extension_of(path: &Path) -> String {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+#[cfg(feature = "svg")]
+fn decode_svg(data: &[u8], target_width: u32, target_height: u32) -> Option<RgbaImage> {
+    let options = usvg::Options::default();
+    let tree = usvg::Tree::from_data(data, &options).ok()?;
+
+    let size = tree.size();
+    let (target_width, target_height) = (target_width.max(1), target_height.max(1));
+    // A single uniform scale (as `resize_to_fit` uses for raster images)
+    // keeps the SVG's aspect ratio instead of stretching it to fill the box.
+    let scale = (target_width as f32 / size.width()).min(target_height as f32 / size.height());
+    let width = ((size.width() * scale).round() as u32).max(1);
+    let height = ((size.height() * scale).round() as u32).max(1);
+    let transform = usvg::Transform::from_scale(scale, scale);
+
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)?;
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    RgbaImage::from_raw(width, height, pixmap.take())
+}
+
+#[cfg(feature = "avif")]
+fn decode_avif(data: &[u8]) -> Option<RgbaImage> {
+    let img = libavif_image::read(data).ok()?;
+    Some(img.into_rgba8())
+}
+
+#[cfg(feature = "heif")]
+fn decode_heif(data: &[u8]) -> Option<RgbaImage> {
+    use libheif_rs::{ColorSpace, HeifContext, RgbChroma};
+
+    let ctx = HeifContext::read_from_memory(data).ok()?;
+    let handle = ctx.primary_image_handle().ok()?;
+    let heif_image = handle.decode(ColorSpace::Rgb(RgbChroma::Rgba), None).ok()?;
+    let plane = heif_image.planes().interleaved?;
+
+    // `plane.data` is `height * stride` bytes, and libheif commonly pads
+    // each row to an alignment boundary, so `stride` can be wider than
+    // `width * 4`; copy row-by-row rather than assuming a packed buffer.
+    let row_bytes = plane.width as usize * 4;
+    let mut buffer = Vec::with_capacity(row_bytes * plane.height as usize);
+    for row in plane.data.chunks(plane.stride) {
+        buffer.extend_from_slice(&row[..row_bytes]);
+    }
+
+    RgbaImage::from_raw(plane.width, plane.height, buffer)
+}
+
+#[cfg(feature = "webp")]
+fn decode_webp(data: &[u8]) -> Option<RgbaImage> {
+    let decoder = webp::Decoder::new(data);
+    let webp_image = decoder.decode()?;
+    Some(webp_image.to_image().into_rgba8())
+}
@@ -17,10 +17,27 @@
 */
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+// The FocusScope/VerticalLayout/ScrollView nest in the slint! macro below
+// resolves its width and height through a harmless cycle (each one
+// stretches to fill its parent); Slint flags that as a deprecated
+// binding-loop pattern even though it converges, so silence the warning
+// crate-wide rather than fight the layout solver (the lint can't be
+// scoped to the macro's generated items).
+#![allow(deprecated)]
+
+mod archive;
+mod cache;
+mod config;
+mod decode;
+mod metadata;
+mod phash;
+mod resize;
 
 use slint::SharedPixelBuffer;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::{cell::RefCell, fs};
 
 slint::slint! {
@@ -35,180 +52,637 @@ slint::slint! {
         in-out property <image> image;
         in-out property <float> zoom: 1.0;
         in-out property <string> filename: "";
+        in-out property <string> metadata-text: "";
+        in-out property <bool> metadata-open: false;
+
+        in-out property <string> key-previous: "Left";
+        in-out property <string> key-next: "Right";
+        in-out property <string> key-zoom-in: "+";
+        in-out property <string> key-zoom-out: "-";
+        in-out property <string> key-zoom-reset: "0";
+        in-out property <string> key-first: "Home";
+        in-out property <string> key-last: "End";
 
         callback load_image(string);
+        callback zoom_changed(float);
+        callback find_similar();
 
-        vl := VerticalLayout {
-            label := Text {
-                text: filename;
-                x: 25px;
-                height: 50px;
-                vertical-alignment: center;
-            }
+        fs := FocusScope {
+            width: 100%;
+            height: 100%;
 
-            sv := ScrollView {
-                width: parent.width;
-                height: parent.height - (label.height + control.height);
-                viewport-width: parent.width * zoom;
-                viewport-height: (parent.height - (label.height + control.height)) * zoom;
-
-                img := Image {
-                    source: image;
-                    width: parent.viewport-width;
-                    height: parent.viewport-height;
-                    image-fit: contain;
+            key-pressed(event) => {
+                if (event.text == root.key-previous) {
+                    root.load_image("previous");
+                    accept
+                } else if (event.text == root.key-next) {
+                    root.load_image("next");
+                    accept
+                } else if (event.text == root.key-first) {
+                    root.load_image("first");
+                    accept
+                } else if (event.text == root.key-last) {
+                    root.load_image("last");
+                    accept
+                } else if (event.text == root.key-zoom-in) {
+                    zoom *= 1.2;
+                    root.zoom_changed(zoom);
+                    accept
+                } else if (event.text == root.key-zoom-out) {
+                    zoom /= 1.2;
+                    root.zoom_changed(zoom);
+                    accept
+                } else if (event.text == root.key-zoom-reset) {
+                    zoom = 1.0;
+                    sv.viewport-x = 0;
+                    sv.viewport-y = 0;
+                    root.zoom_changed(zoom);
+                    accept
+                } else {
+                    reject
                 }
             }
 
-            control := HorizontalLayout {
-                width: parent.width;
-                height: 50px;
-                alignment: center;
-
-                Button {
-                    text: "Previous";
-                    clicked => { root.load_image("previous"); }
+            vl := VerticalLayout {
+                label := Text {
+                    text: filename;
+                    x: 25px;
+                    height: 50px;
+                    vertical-alignment: center;
                 }
-                Button {
-                    text: "Zoom In";
-                    clicked => {
-                        zoom *= 1.2;
+
+                panel := Rectangle {
+                    height: metadata-open ? 120px : 0px;
+                    clip: true;
+                    background: #000000aa;
+
+                    Text {
+                        text: metadata-text;
+                        x: 10px;
+                        y: 10px;
+                        color: white;
                     }
                 }
-                Button {
-                    text: "Zoom Out";
-                    clicked => {
-                        zoom /= 1.2;
+
+                sv := ScrollView {
+                    width: parent.width;
+                    height: parent.height - (label.height + control.height + panel.height);
+                    viewport-width: parent.width * zoom;
+                    viewport-height: (parent.height - (label.height + control.height + panel.height)) * zoom;
+
+                    img := Image {
+                        source: image;
+                        width: parent.viewport-width;
+                        height: parent.viewport-height;
+                        image-fit: contain;
                     }
                 }
-                Button {
-                    text: "Zoom Reset";
-                    clicked => {
-                        zoom = 1.0;
-                        sv.viewport-x = 0;
-                        sv.viewport-y = 0;
+
+                control := HorizontalLayout {
+                    width: parent.width;
+                    height: 50px;
+                    alignment: center;
+
+                    Button {
+                        text: "Previous";
+                        clicked => { root.load_image("previous"); }
+                    }
+                    Button {
+                        text: "Zoom In";
+                        clicked => {
+                            zoom *= 1.2;
+                            root.zoom_changed(zoom);
+                        }
+                    }
+                    Button {
+                        text: "Zoom Out";
+                        clicked => {
+                            zoom /= 1.2;
+                            root.zoom_changed(zoom);
+                        }
+                    }
+                    Button {
+                        text: "Zoom Reset";
+                        clicked => {
+                            zoom = 1.0;
+                            sv.viewport-x = 0;
+                            sv.viewport-y = 0;
+                            root.zoom_changed(zoom);
+                        }
+                    }
+                    Button {
+                        text: "Next";
+                        clicked => { root.load_image("next"); }
+                    }
+                    Button {
+                        text: metadata-open ? "Hide Info" : "Info";
+                        clicked => { metadata-open = !metadata-open; }
+                    }
+                    Button {
+                        text: "Find Similar";
+                        clicked => { root.find_similar(); }
                     }
-                }
-                Button {
-                    text: "Next";
-                    clicked => { root.load_image("next"); }
                 }
             }
         }
+
+        init => {
+            fs.focus();
+        }
+    }
+}
+
+/// Where the current set of pages comes from: a plain directory of image
+/// files, or the entries of a ZIP/CBZ comic archive.
+#[derive(Debug, Clone)]
+enum ImageSource {
+    Directory(Vec<PathBuf>),
+    Archive(archive::ComicArchive),
+}
+
+impl ImageSource {
+    fn len(&self) -> usize {
+        match self {
+            ImageSource::Directory(images) => images.len(),
+            ImageSource::Archive(archive) => archive.len(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 }
 
 #[derive(Debug, Clone)]
 struct ImageViewer {
-    images: Vec<PathBuf>,
+    source: ImageSource,
     current_index: usize,
+    current_metadata: metadata::ImageMetadata,
+    current_zoom: f32,
+    /// The full-resolution decode of the current raster image, kept around so
+    /// zooming in re-resizes from the source instead of upscaling an
+    /// already-downsampled bitmap. `None` for vector images in a directory,
+    /// which rasterize straight to the target size instead; for a vector
+    /// archive entry this holds its rasterization at the current target size.
+    source_image: Option<image::RgbaImage>,
+    /// Decoded neighbors of the current directory image, kept warm so
+    /// `Next`/`Previous` usually don't have to touch the disk.
+    cache: Arc<cache::PreloadCache>,
+    /// Perceptual hashes computed so far, keyed by path. Filled in lazily as
+    /// images are decoded rather than up front for the whole folder.
+    hashes: Arc<Mutex<HashMap<PathBuf, phash::Hash>>>,
+    /// Indices "Find Similar" has already jumped to or shown, so it keeps
+    /// exploring instead of bouncing between the same two images.
+    visited: HashSet<usize>,
+    /// Maximum dHash Hamming distance "Find Similar" still treats as a
+    /// match, configurable via [`config::KeyBindings::similarity_threshold`].
+    similarity_threshold: u32,
 }
 
 impl ImageViewer {
-    fn new(images: Vec<PathBuf>, current_index: usize) -> Self {
+    fn new(images: Vec<PathBuf>, current_index: usize, similarity_threshold: u32) -> Self {
+        Self::from_source(
+            ImageSource::Directory(images),
+            current_index,
+            similarity_threshold,
+        )
+    }
+
+    fn new_archive(archive: archive::ComicArchive, similarity_threshold: u32) -> Self {
+        Self::from_source(ImageSource::Archive(archive), 0, similarity_threshold)
+    }
+
+    fn from_source(source: ImageSource, current_index: usize, similarity_threshold: u32) -> Self {
         Self {
-            images,
+            source,
             current_index,
+            current_metadata: metadata::ImageMetadata::default(),
+            current_zoom: 1.0,
+            source_image: None,
+            cache: Arc::new(cache::PreloadCache::default()),
+            hashes: Arc::new(Mutex::new(HashMap::new())),
+            visited: HashSet::new(),
+            similarity_threshold,
         }
     }
 
-    fn load_image(&mut self, direction: &str) -> Option<slint::Image> {
-        if self.images.is_empty() {
+    fn load_image(
+        &mut self,
+        direction: &str,
+        viewport_width: u32,
+        viewport_height: u32,
+    ) -> Option<slint::Image> {
+        if self.source.is_empty() {
             return None;
         }
+        let len = self.source.len();
 
         match direction {
             "previous" => {
-                if self.current_index == 0 {
-                    self.current_index = self.images.len() - 1;
+                self.current_index = if self.current_index == 0 {
+                    len - 1
                 } else {
-                    self.current_index -= 1;
-                }
+                    self.current_index - 1
+                };
+                self.cache.bump_generation();
             }
             "next" => {
-                self.current_index = (self.current_index + 1) % self.images.len();
+                self.current_index = (self.current_index + 1) % len;
+                self.cache.bump_generation();
+            }
+            "first" => {
+                self.current_index = 0;
+                self.cache.bump_generation();
+            }
+            "last" => {
+                self.current_index = len - 1;
+                self.cache.bump_generation();
             }
             _ => {}
         }
 
-        let image_path = &self.images[self.current_index];
-        let img = image::open(image_path).ok()?;
-        let img = img.to_rgba8();
-        let (width, height) = img.dimensions();
-        let buffer = SharedPixelBuffer::clone_from_slice(&img, width, height);
-        let img = slint::Image::from_rgba8(buffer);
+        let image = self.reload_current(viewport_width, viewport_height);
+        self.spawn_preloads();
+        image
+    }
+
+    /// Updates the zoom factor and re-renders the current image for it:
+    /// vector images are re-rasterized at the new target size, while raster
+    /// images are re-resized from the cached full-resolution source.
+    fn set_zoom(
+        &mut self,
+        zoom: f32,
+        viewport_width: u32,
+        viewport_height: u32,
+    ) -> Option<slint::Image> {
+        self.current_zoom = zoom;
+        if self.current_is_vector() {
+            self.reload_current(viewport_width, viewport_height)
+        } else {
+            self.resize_current(viewport_width, viewport_height)
+        }
+    }
+
+    fn current_is_vector(&self) -> bool {
+        match &self.source {
+            ImageSource::Directory(images) => images
+                .get(self.current_index)
+                .is_some_and(|path| decode::is_vector(path)),
+            ImageSource::Archive(archive) => archive
+                .entry_name(self.current_index)
+                .is_some_and(|name| decode::is_vector(Path::new(name))),
+        }
+    }
+
+    /// Target size for the current zoom level: the viewport scaled by
+    /// `current_zoom`, which both the SVG rasterizer and the raster resize
+    /// step treat as an upper bound.
+    fn target_size(&self, viewport_width: u32, viewport_height: u32) -> (u32, u32) {
+        let width = (viewport_width.max(1) as f32 * self.current_zoom)
+            .round()
+            .max(1.0) as u32;
+        let height = (viewport_height.max(1) as f32 * self.current_zoom)
+            .round()
+            .max(1.0) as u32;
+        (width, height)
+    }
+
+    fn reload_current(
+        &mut self,
+        viewport_width: u32,
+        viewport_height: u32,
+    ) -> Option<slint::Image> {
+        let (target_width, target_height) = self.target_size(viewport_width, viewport_height);
+
+        match &self.source {
+            ImageSource::Directory(images) => {
+                let image_path = images.get(self.current_index)?.clone();
+
+                if decode::is_vector(&image_path) {
+                    self.source_image = None;
+                    let img = decode::decode(&image_path, target_width, target_height)?;
+                    self.current_metadata =
+                        metadata::read_metadata(&image_path, img.width(), img.height());
+                    self.record_hash(&image_path, &img);
+                    self.visited.insert(self.current_index);
+                    return Some(to_slint_image(img));
+                }
+
+                let img = if let Some(cached) = self.cache.get(&image_path) {
+                    cached
+                } else {
+                    let img = decode_and_orient(&image_path)?;
+                    self.cache.insert_if_current(
+                        self.cache.generation(),
+                        image_path.clone(),
+                        img.clone(),
+                    );
+                    img
+                };
+                self.current_metadata =
+                    metadata::read_metadata(&image_path, img.width(), img.height());
+                self.record_hash(&image_path, &img);
+                self.source_image = Some(img);
+            }
+            ImageSource::Archive(archive) => {
+                let entry_name = archive.entry_name(self.current_index)?.to_owned();
+                let bytes = archive.read_entry(self.current_index)?;
+                let extension = decode::extension_of(Path::new(&entry_name));
+                let img =
+                    decode::decode_from_memory(&bytes, &extension, target_width, target_height)?;
+                let orientation = metadata::read_orientation_from_memory(&bytes);
+                let img = metadata::apply_orientation(img, orientation);
+                self.current_metadata =
+                    metadata::read_metadata_from_memory(&bytes, img.width(), img.height());
+                self.source_image = Some(img);
+            }
+        }
+
+        self.visited.insert(self.current_index);
+        self.resize_current(viewport_width, viewport_height)
+    }
+
+    /// Records the perceptual hash for `path` the first time it's seen,
+    /// piggy-backing on a decode that already happened rather than causing
+    /// an extra one.
+    fn record_hash(&self, path: &Path, img: &image::RgbaImage) {
+        self.hashes
+            .lock()
+            .unwrap()
+            .entry(path.to_owned())
+            .or_insert_with(|| phash::dhash(img));
+    }
+
+    /// Re-resizes the cached full-resolution source for the current zoom
+    /// level, without touching the disk.
+    fn resize_current(
+        &mut self,
+        viewport_width: u32,
+        viewport_height: u32,
+    ) -> Option<slint::Image> {
+        let (target_width, target_height) = self.target_size(viewport_width, viewport_height);
+        let resized =
+            resize::resize_to_fit(self.source_image.as_ref()?, target_width, target_height);
+        Some(to_slint_image(resized))
+    }
+
+    fn previous_index(&self) -> usize {
+        if self.current_index == 0 {
+            self.source.len() - 1
+        } else {
+            self.current_index - 1
+        }
+    }
+
+    fn next_index(&self) -> usize {
+        (self.current_index + 1) % self.source.len()
+    }
+
+    /// Decodes `current_index ± 1` on background threads and drops them into
+    /// the cache, so by the time the user clicks `Next`/`Previous` the page
+    /// is usually already there.
+    fn spawn_preloads(&self) {
+        let ImageSource::Directory(images) = &self.source else {
+            return;
+        };
+        if images.len() < 2 {
+            return;
+        }
+
+        let generation = self.cache.generation();
+        for neighbor_index in [self.previous_index(), self.next_index()] {
+            let Some(path) = images.get(neighbor_index).cloned() else {
+                continue;
+            };
+            if decode::is_vector(&path) || self.cache.get(&path).is_some() {
+                continue;
+            }
+
+            let cache = Arc::clone(&self.cache);
+            let hashes = Arc::clone(&self.hashes);
+            std::thread::spawn(move || {
+                if let Some(img) = decode_and_orient(&path) {
+                    hashes
+                        .lock()
+                        .unwrap()
+                        .entry(path.clone())
+                        .or_insert_with(|| phash::dhash(&img));
+                    cache.insert_if_current(generation, path, img);
+                }
+            });
+        }
+    }
+
+    /// Jumps to the unvisited image whose dHash is closest to the current
+    /// one's, as long as that distance is within `self.similarity_threshold`.
+    /// Hashes for images that haven't been decoded yet are computed on the
+    /// spot. Only supported for directories, not archive pages.
+    fn find_similar(&mut self, viewport_width: u32, viewport_height: u32) -> Option<slint::Image> {
+        let ImageSource::Directory(images) = &self.source else {
+            return None;
+        };
+        let images = images.clone();
+        if images.len() < 2 {
+            return None;
+        }
+
+        let current_path = images.get(self.current_index)?.clone();
+        let current_hash = self.hash_for_path(&current_path)?;
+
+        let mut best: Option<(usize, u32)> = None;
+        for (index, path) in images.iter().enumerate() {
+            if index == self.current_index || self.visited.contains(&index) {
+                continue;
+            }
+            let Some(hash) = self.hash_for_path(path) else {
+                continue;
+            };
+            let distance = phash::hamming_distance(current_hash, hash);
+            let is_closer = best
+                .map(|(_, best_distance)| distance < best_distance)
+                .unwrap_or(true);
+            if distance <= self.similarity_threshold && is_closer {
+                best = Some((index, distance));
+            }
+        }
+
+        let (index, _) = best?;
+        self.current_index = index;
+        self.cache.bump_generation();
+        let image = self.reload_current(viewport_width, viewport_height);
+        self.spawn_preloads();
+        image
+    }
 
-        Some(img)
+    /// Returns the cached hash for `path`, decoding and hashing it on demand
+    /// if it hasn't been seen yet.
+    fn hash_for_path(&self, path: &Path) -> Option<phash::Hash> {
+        if let Some(hash) = self.hashes.lock().unwrap().get(path).copied() {
+            return Some(hash);
+        }
+        let img = self.cache.get(path).or_else(|| decode_and_orient(path))?;
+        let hash = phash::dhash(&img);
+        self.hashes.lock().unwrap().insert(path.to_owned(), hash);
+        Some(hash)
     }
 
     fn filename(&self) -> String {
-        self.images
-            .get(self.current_index)
-            .and_then(|p| p.file_name())
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|| "".to_string())
+        match &self.source {
+            ImageSource::Directory(images) => images
+                .get(self.current_index)
+                .and_then(|p| p.file_name())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "".to_string()),
+            ImageSource::Archive(archive) => archive
+                .entry_name(self.current_index)
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| "".to_string()),
+        }
+    }
+
+    fn metadata_text(&self) -> String {
+        self.current_metadata.to_display_string()
     }
 }
 
+/// Decodes a raster image at its native resolution and applies its EXIF
+/// orientation. Shared by the synchronous load path and the background
+/// preloader so both cache the same kind of entry.
+fn decode_and_orient(path: &Path) -> Option<image::RgbaImage> {
+    let img = decode::decode(path, 0, 0)?;
+    let orientation = metadata::read_orientation(path);
+    Some(metadata::apply_orientation(img, orientation))
+}
+
+fn to_slint_image(img: image::RgbaImage) -> slint::Image {
+    let (width, height) = img.dimensions();
+    let buffer = SharedPixelBuffer::clone_from_slice(&img, width, height);
+    slint::Image::from_rgba8(buffer)
+}
+
 fn main() {
     let path: PathBuf = std::env::args()
-        .skip(1)
-        .next()
+        .nth(1)
         .unwrap_or(".".to_string())
         .into();
 
-    let (image_path, image_dir) = if path.is_dir() {
-        (None, path)
+    let archive_path = if archive::is_archive_path(&path) {
+        Some(path.clone())
+    } else if path.is_dir() {
+        find_first_archive(&path)
     } else {
-        let dir = path
-            .parent()
-            .map(|p| p.to_owned())
-            .unwrap_or_else(|| ".".into());
-        (Some(path), dir)
+        None
     };
 
-    let images = fs::read_dir(image_dir)
-        .unwrap()
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            let path = entry.path();
-            if path.extension()?.to_str()?.eq_ignore_ascii_case("png")
-                || path.extension()?.to_str()?.eq_ignore_ascii_case("jpg")
-                || path.extension()?.to_str()?.eq_ignore_ascii_case("jpeg")
-            {
-                Some(path)
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
+    let key_bindings = config::KeyBindings::load();
 
-    let image_index = if let Some(image_path) = image_path {
-        images.iter().position(|p| p == &image_path).unwrap_or(0)
+    let viewer = if let Some(archive_path) = archive_path {
+        let Some(archive) = archive::ComicArchive::open(&archive_path) else {
+            eprintln!("failed to open archive {}", archive_path.display());
+            std::process::exit(1);
+        };
+        ImageViewer::new_archive(archive, key_bindings.similarity_threshold)
     } else {
-        0
+        let (image_path, image_dir) = if path.is_dir() {
+            (None, path)
+        } else {
+            let dir = path
+                .parent()
+                .map(|p| p.to_owned())
+                .unwrap_or_else(|| ".".into());
+            (Some(path), dir)
+        };
+
+        let images = fs::read_dir(image_dir)
+            .unwrap()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let path = entry.path();
+                let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+                if decode::is_supported_extension(&extension) {
+                    Some(path)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let image_index = if let Some(image_path) = image_path {
+            images.iter().position(|p| p == &image_path).unwrap_or(0)
+        } else {
+            0
+        };
+
+        ImageViewer::new(images, image_index, key_bindings.similarity_threshold)
     };
 
-    let viewer = Rc::new(RefCell::new(ImageViewer::new(images, image_index)));
+    let viewer = Rc::new(RefCell::new(viewer));
     let main_window = MainWindow::new().unwrap();
 
+    main_window.set_key_previous(config::resolve_key_text(&key_bindings.previous).into());
+    main_window.set_key_next(config::resolve_key_text(&key_bindings.next).into());
+    main_window.set_key_zoom_in(config::resolve_key_text(&key_bindings.zoom_in).into());
+    main_window.set_key_zoom_out(config::resolve_key_text(&key_bindings.zoom_out).into());
+    main_window.set_key_zoom_reset(config::resolve_key_text(&key_bindings.zoom_reset).into());
+    main_window.set_key_first(config::resolve_key_text(&key_bindings.first).into());
+    main_window.set_key_last(config::resolve_key_text(&key_bindings.last).into());
+
     main_window.on_load_image({
         let main_window = main_window.as_weak();
         let viewer = Rc::clone(&viewer);
         move |direction| {
             let main_window = main_window.unwrap();
-            if let Some((image, filename)) = load_image_and_filename(&viewer, &direction) {
+            let (viewport_width, viewport_height) = viewport_size(&main_window);
+            if let Some((image, filename, metadata_text)) =
+                load_image_and_filename(&viewer, &direction, viewport_width, viewport_height)
+            {
                 main_window.set_image(image);
                 main_window.set_filename(filename.into());
+                main_window.set_metadata_text(metadata_text.into());
             } else {
                 main_window.set_filename("empty".into());
+                main_window.set_metadata_text("".into());
             }
         }
     });
 
-    if let Some((image, filename)) = load_image_and_filename(&viewer, "") {
+    main_window.on_zoom_changed({
+        let main_window = main_window.as_weak();
+        let viewer = Rc::clone(&viewer);
+        move |zoom| {
+            let main_window = main_window.unwrap();
+            let (viewport_width, viewport_height) = viewport_size(&main_window);
+            if let Ok(mut viewer) = viewer.try_borrow_mut() {
+                if let Some(image) = viewer.set_zoom(zoom, viewport_width, viewport_height) {
+                    main_window.set_image(image);
+                }
+            }
+        }
+    });
+
+    main_window.on_find_similar({
+        let main_window = main_window.as_weak();
+        let viewer = Rc::clone(&viewer);
+        move || {
+            let main_window = main_window.unwrap();
+            let (viewport_width, viewport_height) = viewport_size(&main_window);
+            if let Some((image, filename, metadata_text)) =
+                find_similar_and_filename(&viewer, viewport_width, viewport_height)
+            {
+                main_window.set_image(image);
+                main_window.set_filename(filename.into());
+                main_window.set_metadata_text(metadata_text.into());
+            }
+        }
+    });
+
+    let (viewport_width, viewport_height) = viewport_size(&main_window);
+    if let Some((image, filename, metadata_text)) =
+        load_image_and_filename(&viewer, "", viewport_width, viewport_height)
+    {
         main_window.set_image(image);
         main_window.set_filename(filename.into());
+        main_window.set_metadata_text(metadata_text.into());
     } else {
         main_window.set_filename("empty".into());
     }
@@ -216,16 +690,54 @@ fn main() {
     main_window.run().unwrap();
 }
 
+/// Looks for a `.zip`/`.cbz` archive directly inside `dir`, so pointing the
+/// viewer at a folder of comic volumes opens the first one instead of
+/// scanning for loose image files. Returns the alphabetically-first match.
+fn find_first_archive(dir: &PathBuf) -> Option<PathBuf> {
+    let mut archives: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| archive::is_archive_path(path))
+        .collect();
+    archives.sort();
+    archives.into_iter().next()
+}
+
+/// Current window size in physical pixels, used as the upper bound for
+/// decoding/resizing so we never render more pixels than can be shown.
+fn viewport_size(main_window: &MainWindow) -> (u32, u32) {
+    let size = main_window.window().size();
+    (size.width, size.height)
+}
+
 fn load_image_and_filename(
     viewer: &Rc<RefCell<ImageViewer>>,
     direction: &str,
-) -> Option<(slint::Image, String)> {
+    viewport_width: u32,
+    viewport_height: u32,
+) -> Option<(slint::Image, String, String)> {
+    viewer
+        .try_borrow_mut()
+        .ok()
+        .and_then(|mut v| v.load_image(direction, viewport_width, viewport_height))
+        .and_then(|image| {
+            let viewer = viewer.try_borrow().ok()?;
+            Some((image, viewer.filename(), viewer.metadata_text()))
+        })
+}
+
+fn find_similar_and_filename(
+    viewer: &Rc<RefCell<ImageViewer>>,
+    viewport_width: u32,
+    viewport_height: u32,
+) -> Option<(slint::Image, String, String)> {
     viewer
         .try_borrow_mut()
         .ok()
-        .and_then(|mut v| v.load_image(direction))
+        .and_then(|mut v| v.find_similar(viewport_width, viewport_height))
         .and_then(|image| {
-            let filename = viewer.try_borrow().ok()?.filename();
-            Some((image, filename))
+            let viewer = viewer.try_borrow().ok()?;
+            Some((image, viewer.filename(), viewer.metadata_text()))
         })
 }
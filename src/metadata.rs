@@ -0,0 +1,232 @@
+/*
+    The simple image viewer.
+    Copyright (C) 2024 Sifi Takashina <sifyfy@sifyfy.dev>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! EXIF orientation handling and the metadata shown in the info panel.
+
+use image::RgbaImage;
+use std::path::Path;
+
+/// The 8 possible values of the EXIF `Orientation` tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Normal,
+    FlipHorizontal,
+    Rotate180,
+    FlipVertical,
+    Transpose,
+    Rotate90,
+    Transverse,
+    Rotate270,
+}
+
+impl Orientation {
+    fn from_tag_value(value: u32) -> Self {
+        match value {
+            2 => Orientation::FlipHorizontal,
+            3 => Orientation::Rotate180,
+            4 => Orientation::FlipVertical,
+            5 => Orientation::Transpose,
+            6 => Orientation::Rotate90,
+            7 => Orientation::Transverse,
+            8 => Orientation::Rotate270,
+            _ => Orientation::Normal,
+        }
+    }
+}
+
+/// Metadata surfaced in the info panel.
+#[derive(Debug, Clone, Default)]
+pub struct ImageMetadata {
+    pub camera_model: Option<String>,
+    pub exposure_time: Option<String>,
+    pub iso: Option<String>,
+    pub gps: Option<String>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ImageMetadata {
+    /// Renders the metadata as the multi-line text the info panel displays.
+    pub fn to_display_string(&self) -> String {
+        let mut lines = vec![format!("Dimensions: {}×{}", self.width, self.height)];
+        if let Some(model) = &self.camera_model {
+            lines.push(format!("Camera: {model}"));
+        }
+        if let Some(exposure) = &self.exposure_time {
+            lines.push(format!("Exposure: {exposure}"));
+        }
+        if let Some(iso) = &self.iso {
+            lines.push(format!("ISO: {iso}"));
+        }
+        if let Some(gps) = &self.gps {
+            lines.push(format!("GPS: {gps}"));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Reads the EXIF orientation tag for `path`, defaulting to `Normal` when the
+/// file has no EXIF data or the tag is absent.
+pub fn read_orientation(path: &Path) -> Orientation {
+    orientation_from_exif(read_exif(path))
+}
+
+/// Like [`read_orientation`], but for EXIF data embedded in an in-memory
+/// buffer rather than a file on disk (used for archive entries).
+pub fn read_orientation_from_memory(data: &[u8]) -> Orientation {
+    orientation_from_exif(read_exif_from_memory(data))
+}
+
+fn orientation_from_exif(exif: Option<exif::Exif>) -> Orientation {
+    exif.and_then(|exif| {
+        exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+            .cloned()
+    })
+    .and_then(|field| field.value.get_uint(0))
+    .map(Orientation::from_tag_value)
+    .unwrap_or(Orientation::Normal)
+}
+
+/// Reads the subset of EXIF fields shown in the info panel.
+pub fn read_metadata(path: &Path, width: u32, height: u32) -> ImageMetadata {
+    metadata_from_exif(read_exif(path), width, height)
+}
+
+/// Like [`read_metadata`], but for EXIF data embedded in an in-memory buffer
+/// rather than a file on disk (used for archive entries).
+pub fn read_metadata_from_memory(data: &[u8], width: u32, height: u32) -> ImageMetadata {
+    metadata_from_exif(read_exif_from_memory(data), width, height)
+}
+
+fn metadata_from_exif(exif: Option<exif::Exif>, width: u32, height: u32) -> ImageMetadata {
+    let camera_model = exif.as_ref().and_then(|exif| {
+        exif.get_field(exif::Tag::Model, exif::In::PRIMARY)
+            .map(|field| field.display_value().to_string())
+    });
+    let exposure_time = exif.as_ref().and_then(|exif| {
+        exif.get_field(exif::Tag::ExposureTime, exif::In::PRIMARY)
+            .map(|field| field.display_value().with_unit(exif).to_string())
+    });
+    let iso = exif.as_ref().and_then(|exif| {
+        exif.get_field(exif::Tag::PhotographicSensitivity, exif::In::PRIMARY)
+            .map(|field| field.display_value().to_string())
+    });
+    let gps = exif.as_ref().and_then(|exif| {
+        let lat = exif.get_field(exif::Tag::GPSLatitude, exif::In::PRIMARY)?;
+        let lon = exif.get_field(exif::Tag::GPSLongitude, exif::In::PRIMARY)?;
+        Some(format!("{}, {}", lat.display_value(), lon.display_value()))
+    });
+
+    ImageMetadata {
+        camera_model,
+        exposure_time,
+        iso,
+        gps,
+        width,
+        height,
+    }
+}
+
+fn read_exif(path: &Path) -> Option<exif::Exif> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut bufreader = std::io::BufReader::new(file);
+    exif::Reader::new().read_from_container(&mut bufreader).ok()
+}
+
+fn read_exif_from_memory(data: &[u8]) -> Option<exif::Exif> {
+    let mut cursor = std::io::Cursor::new(data);
+    exif::Reader::new().read_from_container(&mut cursor).ok()
+}
+
+/// Applies the EXIF orientation transform to a decoded RGBA buffer so the
+/// pixels end up displayed the right way round regardless of how the camera
+/// held the sensor.
+pub fn apply_orientation(img: RgbaImage, orientation: Orientation) -> RgbaImage {
+    use image::imageops::{flip_horizontal, flip_vertical, rotate180, rotate270, rotate90};
+
+    match orientation {
+        Orientation::Normal => img,
+        Orientation::FlipHorizontal => flip_horizontal(&img),
+        Orientation::Rotate180 => rotate180(&img),
+        Orientation::FlipVertical => flip_vertical(&img),
+        Orientation::Transpose => flip_horizontal(&rotate90(&img)),
+        Orientation::Rotate90 => rotate90(&img),
+        Orientation::Transverse => flip_horizontal(&rotate270(&img)),
+        Orientation::Rotate270 => rotate270(&img),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::imageops::{flip_horizontal, flip_vertical, rotate180, rotate270, rotate90};
+
+    fn sample_image() -> RgbaImage {
+        RgbaImage::from_fn(3, 2, |x, y| {
+            image::Rgba([(x * 10) as u8, (y * 10) as u8, 0, 255])
+        })
+    }
+
+    #[test]
+    fn normal_orientation_is_a_no_op() {
+        let img = sample_image();
+        assert_eq!(apply_orientation(img.clone(), Orientation::Normal), img);
+    }
+
+    #[test]
+    fn each_orientation_matches_its_imageops_transform() {
+        let img = sample_image();
+        assert_eq!(
+            apply_orientation(img.clone(), Orientation::FlipHorizontal),
+            flip_horizontal(&img)
+        );
+        assert_eq!(
+            apply_orientation(img.clone(), Orientation::Rotate180),
+            rotate180(&img)
+        );
+        assert_eq!(
+            apply_orientation(img.clone(), Orientation::FlipVertical),
+            flip_vertical(&img)
+        );
+        assert_eq!(
+            apply_orientation(img.clone(), Orientation::Rotate90),
+            rotate90(&img)
+        );
+        assert_eq!(
+            apply_orientation(img.clone(), Orientation::Rotate270),
+            rotate270(&img)
+        );
+        assert_eq!(
+            apply_orientation(img.clone(), Orientation::Transpose),
+            flip_horizontal(&rotate90(&img))
+        );
+        assert_eq!(
+            apply_orientation(img.clone(), Orientation::Transverse),
+            flip_horizontal(&rotate270(&img))
+        );
+    }
+
+    #[test]
+    fn tag_value_maps_to_the_documented_orientation() {
+        assert_eq!(Orientation::from_tag_value(1), Orientation::Normal);
+        assert_eq!(Orientation::from_tag_value(3), Orientation::Rotate180);
+        assert_eq!(Orientation::from_tag_value(6), Orientation::Rotate90);
+        assert_eq!(Orientation::from_tag_value(8), Orientation::Rotate270);
+        assert_eq!(Orientation::from_tag_value(255), Orientation::Normal);
+    }
+}
@@ -0,0 +1,103 @@
+/*
+    The simple image viewer.
+    Copyright (C) 2024 Sifi Takashina <sifyfy@sifyfy.dev>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! 64-bit difference hashing ("dHash"), used to find visually similar
+//! images without the expense of comparing full decoded buffers.
+
+use image::{imageops::FilterType, DynamicImage, RgbaImage};
+
+/// A 64-bit perceptual hash. Two images that look alike tend to differ in
+/// only a handful of bits; see [`hamming_distance`].
+pub type Hash = u64;
+
+/// Computes the dHash of `img`: downscale to grayscale 9x8, then for each of
+/// the 8 rows set a bit per column when the pixel is brighter than its right
+/// neighbor, giving 8x8 = 64 bits.
+pub fn dhash(img: &RgbaImage) -> Hash {
+    let shrunk = DynamicImage::ImageRgba8(img.clone())
+        .resize_exact(9, 8, FilterType::Triangle)
+        .to_luma8();
+
+    let mut hash: Hash = 0;
+    let mut bit = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = shrunk.get_pixel(x, y)[0];
+            let right = shrunk.get_pixel(x + 1, y)[0];
+            if left > right {
+                hash |= 1 << bit;
+            }
+            bit += 1;
+        }
+    }
+    hash
+}
+
+/// Number of differing bits between two hashes; 0 means identical, 64 means
+/// every bit differs.
+pub fn hamming_distance(a: Hash, b: Hash) -> u32 {
+    (a ^ b).count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_images_hash_to_the_same_value() {
+        let img = RgbaImage::from_fn(32, 32, |x, y| {
+            image::Rgba([(x * 7) as u8, (y * 5) as u8, 0, 255])
+        });
+        assert_eq!(dhash(&img), dhash(&img.clone()));
+    }
+
+    #[test]
+    fn hamming_distance_of_identical_hashes_is_zero() {
+        let hash = 0x1234_5678_9abc_def0;
+        assert_eq!(hamming_distance(hash, hash), 0);
+    }
+
+    #[test]
+    fn hamming_distance_counts_differing_bits() {
+        assert_eq!(hamming_distance(0b0000, 0b0000), 0);
+        assert_eq!(hamming_distance(0b0000, 0b0001), 1);
+        assert_eq!(hamming_distance(0b1010, 0b0101), 4);
+        assert_eq!(hamming_distance(u64::MAX, 0), 64);
+    }
+
+    #[test]
+    fn a_descending_brightness_gradient_sets_every_bit() {
+        // Every column is strictly darker than the one to its left, so every
+        // left-vs-right-neighbor comparison in `dhash` comes out the same
+        // way: all 64 bits set.
+        let img = RgbaImage::from_fn(16, 16, |x, _y| {
+            let shade = 255 - (x * 16) as u8;
+            image::Rgba([shade, shade, shade, 255])
+        });
+        assert_eq!(dhash(&img), u64::MAX);
+    }
+
+    #[test]
+    fn an_ascending_brightness_gradient_clears_every_bit() {
+        let img = RgbaImage::from_fn(16, 16, |x, _y| {
+            let shade = (x * 16) as u8;
+            image::Rgba([shade, shade, shade, 255])
+        });
+        assert_eq!(dhash(&img), 0);
+    }
+}
@@ -0,0 +1,97 @@
+/*
+    The simple image viewer.
+    Copyright (C) 2024 Sifi Takashina <sifyfy@sifyfy.dev>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! SIMD-accelerated downscaling so the UI thread never uploads a
+//! full-resolution decode for an image that's only shown at a fraction of
+//! its native size.
+
+use fast_image_resize as fr;
+use fast_image_resize::images::Image;
+use image::RgbaImage;
+
+/// Resizes `img` down to fit within `max_width` x `max_height`, preserving
+/// aspect ratio. Returns `img` unchanged (cloned) if it already fits, since
+/// upscaling a raster source only loses quality without a vector to fall
+/// back to.
+pub fn resize_to_fit(img: &RgbaImage, max_width: u32, max_height: u32) -> RgbaImage {
+    let (width, height) = img.dimensions();
+    if width <= max_width.max(1) && height <= max_height.max(1) {
+        return img.clone();
+    }
+
+    let scale =
+        (max_width.max(1) as f32 / width as f32).min(max_height.max(1) as f32 / height as f32);
+    let target_width = ((width as f32 * scale).round() as u32).max(1);
+    let target_height = ((height as f32 * scale).round() as u32).max(1);
+
+    let src_image = Image::from_vec_u8(width, height, img.clone().into_raw(), fr::PixelType::U8x4)
+        .expect("RGBA8 buffer matches declared dimensions");
+
+    let mut dst_image = Image::new(target_width, target_height, fr::PixelType::U8x4);
+
+    let options =
+        fr::ResizeOptions::new().resize_alg(fr::ResizeAlg::Convolution(fr::FilterType::Lanczos3));
+    let mut resizer = fr::Resizer::new();
+    resizer
+        .resize(&src_image, &mut dst_image, &options)
+        .expect("same pixel type on both sides");
+
+    RgbaImage::from_raw(target_width, target_height, dst_image.into_vec())
+        .expect("resizer produced a buffer matching target dimensions")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid_image(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, image::Rgba([200, 100, 50, 255]))
+    }
+
+    #[test]
+    fn images_already_within_bounds_are_returned_unchanged() {
+        let img = solid_image(100, 50);
+        let resized = resize_to_fit(&img, 200, 200);
+        assert_eq!(resized.dimensions(), (100, 50));
+    }
+
+    #[test]
+    fn downscaling_preserves_aspect_ratio_on_the_limiting_axis() {
+        // 200x100 is wider than it is tall, so fitting it into a 50x50 box
+        // should be limited by width, not height.
+        let img = solid_image(200, 100);
+        let resized = resize_to_fit(&img, 50, 50);
+        assert_eq!(resized.dimensions(), (50, 25));
+    }
+
+    #[test]
+    fn downscaling_limited_by_height_keeps_width_proportional() {
+        // 100x200 is taller than it is wide, so a 50x50 box is limited by
+        // height.
+        let img = solid_image(100, 200);
+        let resized = resize_to_fit(&img, 50, 50);
+        assert_eq!(resized.dimensions(), (25, 50));
+    }
+
+    #[test]
+    fn target_dimensions_never_round_down_to_zero() {
+        let img = solid_image(1000, 1);
+        let resized = resize_to_fit(&img, 1, 1000);
+        assert!(resized.width() >= 1 && resized.height() >= 1);
+    }
+}